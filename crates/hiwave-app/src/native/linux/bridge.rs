@@ -0,0 +1,162 @@
+//! JS↔Rust IPC bridge ("invoke handler") for the native RustKit surface.
+//!
+//! Mirrors the classic `external.invoke("...")` pattern used by other
+//! embeddable WebViews: page JavaScript calls `external.invoke(message)`,
+//! RustKit marshals that string to the UI thread as a [`SurfaceEvent::Invoke`],
+//! and the registered [`InvokeHandler`] runs with a handle back to the
+//! [`NativeView`] so it can reply by [`NativeView::eval`]-ing JS into the page.
+//!
+//! Calls made before the document has finished loading are queued in
+//! [`InvokeQueue`] and replayed, in order, once it is - rather than being
+//! dropped on the floor.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use super::NativeView;
+
+/// A handler registered via [`super::NativeOptions::invoke_handler`] that
+/// receives messages sent from page JavaScript via `external.invoke(...)`.
+pub type InvokeHandler = Arc<dyn Fn(&NativeView, &str) + Send + Sync + 'static>;
+
+/// A single `external.invoke(...)` call received from page JavaScript.
+pub struct InvokeMessage {
+    body: String,
+}
+
+impl InvokeMessage {
+    pub(crate) fn new(body: String) -> Self {
+        Self { body }
+    }
+
+    /// The raw string passed to `external.invoke(...)`.
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+}
+
+/// Buffers `external.invoke` calls made before the document is ready so they
+/// can be replayed, in order, once it is.
+#[derive(Default)]
+pub(crate) struct InvokeQueue {
+    pending: Mutex<VecDeque<InvokeMessage>>,
+    ready: Mutex<bool>,
+}
+
+impl InvokeQueue {
+    fn is_ready(&self) -> bool {
+        *self.ready.lock().unwrap()
+    }
+
+    fn enqueue(&self, message: InvokeMessage) {
+        self.pending.lock().unwrap().push_back(message);
+    }
+
+    fn drain(&self) -> VecDeque<InvokeMessage> {
+        std::mem::take(&mut self.pending.lock().unwrap())
+    }
+
+    /// Marks the queue ready, allowing future `dispatch` calls to run the
+    /// handler synchronously instead of queueing. Each new navigation resets
+    /// this back to `false` (see [`install`]).
+    pub(crate) fn mark_ready(&self) {
+        *self.ready.lock().unwrap() = true;
+    }
+
+    fn reset(&self) {
+        *self.ready.lock().unwrap() = false;
+        self.pending.lock().unwrap().clear();
+    }
+}
+
+/// Registers the `external.invoke` binding on `view`'s document and resets
+/// the invoke queue for the new navigation.
+///
+/// Must be called once per navigation: RustKit clears injected bindings on
+/// each new document, so `run_native`'s event loop re-installs it whenever a
+/// fresh document starts loading.
+pub(super) fn install(view: &NativeView, handler: Option<&InvokeHandler>) {
+    view.invoke_queue.reset();
+    if handler.is_some() {
+        view.surface.register_invoke_binding();
+    }
+}
+
+/// Handles a single `SurfaceEvent::Invoke`, either dispatching it immediately
+/// (document already ready) or queueing it for [`flush`].
+///
+/// `run_native`'s event loop runs on the UI thread, so both the immediate and
+/// queued-then-flushed paths end up calling `handler` there.
+pub(super) fn dispatch(view: &NativeView, handler: Option<&InvokeHandler>, message: InvokeMessage) {
+    let Some(handler) = handler else { return };
+    if view.invoke_queue.is_ready() {
+        handler(view, message.body());
+    } else {
+        view.invoke_queue.enqueue(message);
+    }
+}
+
+/// Replays any `external.invoke` calls queued while the document was loading,
+/// in the order they were received.
+pub(super) fn flush(view: &NativeView, handler: Option<&InvokeHandler>) {
+    let Some(handler) = handler else { return };
+    for message in view.invoke_queue.drain() {
+        handler(view, message.body());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enqueue_all(queue: &InvokeQueue, bodies: &[&str]) {
+        for body in bodies {
+            queue.enqueue(InvokeMessage::new((*body).to_string()));
+        }
+    }
+
+    #[test]
+    fn new_queue_is_not_ready_and_has_nothing_pending() {
+        let queue = InvokeQueue::default();
+        assert!(!queue.is_ready());
+        assert!(queue.drain().is_empty());
+    }
+
+    #[test]
+    fn drain_returns_messages_in_fifo_order() {
+        let queue = InvokeQueue::default();
+        enqueue_all(&queue, &["first", "second", "third"]);
+
+        let bodies: Vec<_> = queue.drain().into_iter().map(|m| m.body().to_string()).collect();
+        assert_eq!(bodies, vec!["first", "second", "third"]);
+    }
+
+    #[test]
+    fn drain_empties_the_queue() {
+        let queue = InvokeQueue::default();
+        enqueue_all(&queue, &["only"]);
+
+        assert_eq!(queue.drain().len(), 1);
+        assert!(queue.drain().is_empty());
+    }
+
+    #[test]
+    fn mark_ready_flips_is_ready() {
+        let queue = InvokeQueue::default();
+        assert!(!queue.is_ready());
+        queue.mark_ready();
+        assert!(queue.is_ready());
+    }
+
+    #[test]
+    fn reset_clears_both_ready_and_pending_messages() {
+        let queue = InvokeQueue::default();
+        enqueue_all(&queue, &["stale"]);
+        queue.mark_ready();
+
+        queue.reset();
+
+        assert!(!queue.is_ready());
+        assert!(queue.drain().is_empty());
+    }
+}
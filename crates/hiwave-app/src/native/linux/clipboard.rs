@@ -0,0 +1,165 @@
+//! System clipboard and primary-selection ("middle-click paste") support.
+//!
+//! Wraps the regular clipboard (Wayland `wl_data_device` / X11 `CLIPBOARD`)
+//! and, separately, the primary selection (`PRIMARY` on X11,
+//! `zwp_primary_selection_v1` on Wayland), following `copypasta`'s split
+//! between the two cross-backend. Both only ever carry
+//! `text/plain;charset=utf-8` - HiWave has no need for richer clipboard MIME
+//! types yet.
+//!
+//! Wayland selection reads are inherently async (offer → request fd → read),
+//! and that read must not block the compositor's event loop. [`Clipboard`]
+//! dispatches each `get_contents`/`set_contents` call onto its own disposable
+//! worker thread - separate from `run_native`'s main surface - rather than
+//! funnelling every call through one long-lived worker: a single shared
+//! worker would let one stalled offer (a hung or hostile source that never
+//! closes its fd) wedge every *future* call too, since nothing else would
+//! ever reach the front of its queue. Per-call threads mean a stalled read
+//! only burns the one thread and the fd it's blocked on; every other call -
+//! including the next one for the same [`SelectionKind`] - still runs
+//! normally. `get_contents`/`set_contents` are synchronous from the caller's
+//! point of view (the realistic caller is `run_native`'s event loop,
+//! servicing a page's paste request via the invoke bridge), so they bound
+//! their own wait with [`SELECTION_TIMEOUT`] and return
+//! [`ClipboardError::Timeout`] rather than blocking forever if their own
+//! worker stalls.
+
+use std::fmt;
+use std::io::Read;
+use std::os::unix::io::FromRawFd;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use rustkit::SelectionBackend;
+
+/// How long `get_contents`/`set_contents` wait for the worker thread before
+/// giving up. Generous enough for a normal offer → request fd → read
+/// round trip, short enough that a stalled source can't freeze the caller.
+const SELECTION_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Which selection buffer an operation targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionKind {
+    /// The regular clipboard (`Ctrl+C` / `Ctrl+V`).
+    Clipboard,
+    /// The X11/Wayland primary selection (middle-click paste).
+    Primary,
+}
+
+/// Error returned by [`Clipboard`] operations.
+#[derive(Debug)]
+pub enum ClipboardError {
+    /// The compositor/X server offered no data for the requested selection.
+    Empty,
+    /// The selection transfer failed at the protocol level.
+    Backend(String),
+    /// The worker didn't reply within [`SELECTION_TIMEOUT`]; the source is
+    /// presumed stalled.
+    Timeout,
+}
+
+impl fmt::Display for ClipboardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClipboardError::Empty => write!(f, "selection is empty"),
+            ClipboardError::Backend(msg) => write!(f, "clipboard error: {msg}"),
+            ClipboardError::Timeout => write!(f, "selection request timed out"),
+        }
+    }
+}
+
+impl std::error::Error for ClipboardError {}
+
+/// Cross-backend clipboard and primary-selection access, exposed to page
+/// content via `run_native`.
+pub struct Clipboard {
+    backend: Arc<SelectionBackend>,
+}
+
+impl Clipboard {
+    /// Wraps `backend` so it can be shared with the disposable per-call
+    /// worker threads `get_contents`/`set_contents` spawn.
+    pub(crate) fn spawn(backend: SelectionBackend) -> Self {
+        Self {
+            backend: Arc::new(backend),
+        }
+    }
+
+    fn read_selection(
+        backend: &SelectionBackend,
+        kind: SelectionKind,
+    ) -> Result<String, ClipboardError> {
+        let fd = backend
+            .request_selection(kind.into())
+            .map_err(|e| ClipboardError::Backend(e.to_string()))?
+            .ok_or(ClipboardError::Empty)?;
+
+        // Takes ownership of the fd the compositor handed back for this
+        // offer; reading it here, off the main thread, is the "read" leg of
+        // the offer → request fd → read hand-off.
+        let mut source = unsafe { std::fs::File::from_raw_fd(fd) };
+        let mut contents = String::new();
+        source
+            .read_to_string(&mut contents)
+            .map(|_| contents)
+            .map_err(|e| ClipboardError::Backend(e.to_string()))
+    }
+
+    /// Reads the current contents of `kind` as UTF-8 text.
+    ///
+    /// Runs on a disposable worker thread and waits for it for at most
+    /// [`SELECTION_TIMEOUT`]; a stalled source yields
+    /// [`ClipboardError::Timeout`] and leaves only that one thread wedged,
+    /// rather than blocking the caller indefinitely or wedging every future
+    /// call the way a single shared worker would.
+    pub fn get_contents(&self, kind: SelectionKind) -> Result<String, ClipboardError> {
+        let backend = Arc::clone(&self.backend);
+        let (reply, result) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = reply.send(Self::read_selection(&backend, kind));
+        });
+        match result.recv_timeout(SELECTION_TIMEOUT) {
+            Ok(outcome) => outcome,
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(ClipboardError::Timeout),
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                Err(ClipboardError::Backend("clipboard worker thread exited".into()))
+            }
+        }
+    }
+
+    /// Replaces the contents of `kind` with `text`, taking ownership of the
+    /// selection.
+    ///
+    /// Runs on a disposable worker thread and waits for it for at most
+    /// [`SELECTION_TIMEOUT`]; a stalled source yields
+    /// [`ClipboardError::Timeout`] and leaves only that one thread wedged,
+    /// rather than blocking the caller indefinitely or wedging every future
+    /// call the way a single shared worker would.
+    pub fn set_contents(&self, kind: SelectionKind, text: String) -> Result<(), ClipboardError> {
+        let backend = Arc::clone(&self.backend);
+        let (reply, result) = mpsc::channel();
+        thread::spawn(move || {
+            let outcome = backend
+                .offer_selection(kind.into(), text)
+                .map_err(|e| ClipboardError::Backend(e.to_string()));
+            let _ = reply.send(outcome);
+        });
+        match result.recv_timeout(SELECTION_TIMEOUT) {
+            Ok(outcome) => outcome,
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(ClipboardError::Timeout),
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                Err(ClipboardError::Backend("clipboard worker thread exited".into()))
+            }
+        }
+    }
+}
+
+impl From<SelectionKind> for rustkit::Selection {
+    fn from(kind: SelectionKind) -> Self {
+        match kind {
+            SelectionKind::Clipboard => rustkit::Selection::Clipboard,
+            SelectionKind::Primary => rustkit::Selection::Primary,
+        }
+    }
+}
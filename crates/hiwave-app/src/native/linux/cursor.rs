@@ -0,0 +1,284 @@
+//! Themed cursor-icon support for interactive content.
+//!
+//! Maps the standard CSS `cursor` keyword set to a shared [`CursorIcon`]
+//! enum and loads the matching shape from the compositor's cursor theme:
+//! the Wayland `wp_cursor_shape_device_v1` protocol where the compositor
+//! advertises it - letting the compositor pick the right bitmap for the
+//! current theme and scale itself, with no client-side loading at all -
+//! falling back to an XCursor theme lookup by name, scaled correctly for
+//! HiDPI, everywhere else (including X11). Loaded XCursor bitmaps are
+//! cached per `(icon, theme, scale)` so switching back to a previously-seen
+//! cursor never re-hits disk.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use rustkit::Surface;
+
+/// The standard CSS `cursor` keyword set, minus the deprecated/non-standard
+/// ones HiWave doesn't need to support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CursorIcon {
+    Default,
+    ContextMenu,
+    Help,
+    Pointer,
+    Progress,
+    Wait,
+    Cell,
+    Crosshair,
+    Text,
+    VerticalText,
+    Alias,
+    Copy,
+    Move,
+    NoDrop,
+    NotAllowed,
+    Grab,
+    Grabbing,
+    ColResize,
+    RowResize,
+    NResize,
+    EResize,
+    SResize,
+    WResize,
+    NeResize,
+    NwResize,
+    SeResize,
+    SwResize,
+    EwResize,
+    NsResize,
+    NeswResize,
+    NwseResize,
+    AllScroll,
+    ZoomIn,
+    ZoomOut,
+}
+
+/// Error returned by [`CursorLoader::set_cursor`].
+#[derive(Debug)]
+pub enum CursorError {
+    /// The XCursor theme has no bitmap for this icon under any of its
+    /// fallback names.
+    NotFound,
+    Backend(String),
+}
+
+impl std::fmt::Display for CursorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CursorError::NotFound => write!(f, "no cursor bitmap for this icon in the theme"),
+            CursorError::Backend(msg) => write!(f, "cursor backend error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for CursorError {}
+
+/// A single loaded, ready-to-upload cursor bitmap (ARGB8888, premultiplied).
+pub struct LoadedCursor {
+    pub width: u32,
+    pub height: u32,
+    pub hotspot_x: u32,
+    pub hotspot_y: u32,
+    pub argb: Vec<u8>,
+}
+
+/// Key identifying one cached XCursor bitmap: the same icon can be cached
+/// separately per theme (the user can change it at runtime) and per HiDPI
+/// scale factor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    icon: CursorIcon,
+    theme: String,
+    scale: u32,
+}
+
+/// Resolves [`CursorIcon`]s to compositor cursor shapes or themed bitmaps,
+/// and caches the latter.
+pub struct CursorLoader {
+    /// Whether the compositor advertises `wp_cursor_shape_manager_v1`; when
+    /// it does, shapes are set by name and no bitmap ever needs loading.
+    supports_cursor_shape: bool,
+    /// Parsed XCursor theme indexes, keyed by theme name, so a given
+    /// theme's `index.theme` and inherited directories are only walked once
+    /// no matter how many distinct icons get loaded from it.
+    themes: Mutex<HashMap<String, Arc<xcursor::CursorTheme>>>,
+    xcursor_cache: Mutex<HashMap<CacheKey, Arc<LoadedCursor>>>,
+}
+
+impl CursorLoader {
+    pub(crate) fn new(supports_cursor_shape: bool) -> Self {
+        Self {
+            supports_cursor_shape,
+            themes: Mutex::new(HashMap::new()),
+            xcursor_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the pointer's cursor to `icon`, using `theme`/`scale` for the
+    /// XCursor fallback path.
+    pub fn set_cursor(
+        &self,
+        surface: &Surface,
+        icon: CursorIcon,
+        theme: &str,
+        scale: u32,
+    ) -> Result<(), CursorError> {
+        if self.supports_cursor_shape {
+            surface
+                .set_cursor_shape(cursor_shape_name(icon))
+                .map_err(|e| CursorError::Backend(e.to_string()))?;
+            return Ok(());
+        }
+
+        let key = CacheKey {
+            icon,
+            theme: theme.to_string(),
+            scale,
+        };
+        if let Some(cursor) = self.xcursor_cache.lock().unwrap().get(&key) {
+            surface
+                .set_cursor_bitmap(cursor)
+                .map_err(|e| CursorError::Backend(e.to_string()))?;
+            return Ok(());
+        }
+
+        let cursor_theme = self.theme(theme);
+        let cursor = Arc::new(load_xcursor(&cursor_theme, icon, scale)?);
+        self.xcursor_cache.lock().unwrap().insert(key, cursor.clone());
+        surface
+            .set_cursor_bitmap(&cursor)
+            .map_err(|e| CursorError::Backend(e.to_string()))
+    }
+
+    /// Returns the parsed index for `theme`, loading (and caching) it on
+    /// first use.
+    fn theme(&self, theme: &str) -> Arc<xcursor::CursorTheme> {
+        if let Some(cached) = self.themes.lock().unwrap().get(theme) {
+            return cached.clone();
+        }
+        let loaded = Arc::new(xcursor::CursorTheme::load(theme));
+        self.themes
+            .lock()
+            .unwrap()
+            .insert(theme.to_string(), loaded.clone());
+        loaded
+    }
+}
+
+/// Loads `icon`'s bitmap from `cursor_theme` at `scale`, trying each of the
+/// icon's legacy alias names (many themes predate the CSS keyword names)
+/// before giving up.
+fn load_xcursor(
+    cursor_theme: &xcursor::CursorTheme,
+    icon: CursorIcon,
+    scale: u32,
+) -> Result<LoadedCursor, CursorError> {
+    let nominal_size = 24 * scale;
+
+    for name in xcursor_names(icon) {
+        let Some(images) = cursor_theme.load_icon(name) else {
+            continue;
+        };
+        let Some(image) = images
+            .iter()
+            .min_by_key(|image| (image.size as i64 - nominal_size as i64).abs())
+        else {
+            continue;
+        };
+
+        return Ok(LoadedCursor {
+            width: image.width,
+            height: image.height,
+            hotspot_x: image.xhot,
+            hotspot_y: image.yhot,
+            argb: image.pixels_argb.clone(),
+        });
+    }
+
+    Err(CursorError::NotFound)
+}
+
+/// The `wp_cursor_shape_device_v1` shape name for `icon` - these match the
+/// CSS keyword 1:1 except for underscores in place of hyphens.
+fn cursor_shape_name(icon: CursorIcon) -> &'static str {
+    match icon {
+        CursorIcon::Default => "default",
+        CursorIcon::ContextMenu => "context_menu",
+        CursorIcon::Help => "help",
+        CursorIcon::Pointer => "pointer",
+        CursorIcon::Progress => "progress",
+        CursorIcon::Wait => "wait",
+        CursorIcon::Cell => "cell",
+        CursorIcon::Crosshair => "crosshair",
+        CursorIcon::Text => "text",
+        CursorIcon::VerticalText => "vertical_text",
+        CursorIcon::Alias => "alias",
+        CursorIcon::Copy => "copy",
+        CursorIcon::Move => "move",
+        CursorIcon::NoDrop => "no_drop",
+        CursorIcon::NotAllowed => "not_allowed",
+        CursorIcon::Grab => "grab",
+        CursorIcon::Grabbing => "grabbing",
+        CursorIcon::ColResize => "col_resize",
+        CursorIcon::RowResize => "row_resize",
+        CursorIcon::NResize => "n_resize",
+        CursorIcon::EResize => "e_resize",
+        CursorIcon::SResize => "s_resize",
+        CursorIcon::WResize => "w_resize",
+        CursorIcon::NeResize => "ne_resize",
+        CursorIcon::NwResize => "nw_resize",
+        CursorIcon::SeResize => "se_resize",
+        CursorIcon::SwResize => "sw_resize",
+        CursorIcon::EwResize => "ew_resize",
+        CursorIcon::NsResize => "ns_resize",
+        CursorIcon::NeswResize => "nesw_resize",
+        CursorIcon::NwseResize => "nwse_resize",
+        CursorIcon::AllScroll => "all_scroll",
+        CursorIcon::ZoomIn => "zoom_in",
+        CursorIcon::ZoomOut => "zoom_out",
+    }
+}
+
+/// XCursor theme names to try for `icon`, in order: the CSS keyword itself
+/// first (modern themes ship it), then older aliases still common in
+/// XCursor themes that predate the CSS spec's naming.
+fn xcursor_names(icon: CursorIcon) -> &'static [&'static str] {
+    match icon {
+        CursorIcon::Default => &["default", "left_ptr"],
+        CursorIcon::ContextMenu => &["context-menu"],
+        CursorIcon::Help => &["help", "question_arrow"],
+        CursorIcon::Pointer => &["pointer", "hand2", "hand1"],
+        CursorIcon::Progress => &["progress", "left_ptr_watch"],
+        CursorIcon::Wait => &["wait", "watch"],
+        CursorIcon::Cell => &["cell", "plus"],
+        CursorIcon::Crosshair => &["crosshair", "cross"],
+        CursorIcon::Text => &["text", "xterm"],
+        CursorIcon::VerticalText => &["vertical-text"],
+        CursorIcon::Alias => &["alias"],
+        CursorIcon::Copy => &["copy"],
+        CursorIcon::Move => &["move"],
+        CursorIcon::NoDrop => &["no-drop"],
+        CursorIcon::NotAllowed => &["not-allowed", "crossed_circle"],
+        CursorIcon::Grab => &["grab", "openhand", "hand1"],
+        CursorIcon::Grabbing => &["grabbing", "closedhand", "hand2"],
+        CursorIcon::ColResize => &["col-resize", "sb_h_double_arrow"],
+        CursorIcon::RowResize => &["row-resize", "sb_v_double_arrow"],
+        CursorIcon::NResize => &["n-resize", "top_side"],
+        CursorIcon::EResize => &["e-resize", "right_side"],
+        CursorIcon::SResize => &["s-resize", "bottom_side"],
+        CursorIcon::WResize => &["w-resize", "left_side"],
+        CursorIcon::NeResize => &["ne-resize", "top_right_corner"],
+        CursorIcon::NwResize => &["nw-resize", "top_left_corner"],
+        CursorIcon::SeResize => &["se-resize", "bottom_right_corner"],
+        CursorIcon::SwResize => &["sw-resize", "bottom_left_corner"],
+        CursorIcon::EwResize => &["ew-resize", "sb_h_double_arrow"],
+        CursorIcon::NsResize => &["ns-resize", "sb_v_double_arrow"],
+        CursorIcon::NeswResize => &["nesw-resize", "fd_double_arrow"],
+        CursorIcon::NwseResize => &["nwse-resize", "bd_double_arrow"],
+        CursorIcon::AllScroll => &["all-scroll", "fleur"],
+        CursorIcon::ZoomIn => &["zoom-in"],
+        CursorIcon::ZoomOut => &["zoom-out"],
+    }
+}
@@ -0,0 +1,209 @@
+//! Delegating external protocols and forced "open externally" navigations to
+//! the platform's default handler.
+//!
+//! HiWave only renders a handful of schemes itself ([`NATIVE_SCHEMES`]);
+//! navigations to anything else - `mailto:`, `tel:`, `magnet:`, custom app
+//! schemes - and `target="_blank"`/"open externally" actions are handed off
+//! to the system browser or URL handler instead, the same way `webbrowser`
+//! does it: `$BROWSER` first, trying each `:`-separated entry in order, then
+//! falling back through `xdg-open`, `gio open`, `gvfs-open`, `gnome-open`.
+//!
+//! Launching never blocks the UI thread: the child is spawned and detached,
+//! and its exit status is reaped on a throwaway thread rather than on the
+//! caller, so `open_externally` returns immediately while the process still
+//! gets waited on instead of turning into a zombie.
+
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Schemes HiWave renders itself; anything else is a candidate for
+/// delegation to the system handler.
+const NATIVE_SCHEMES: &[&str] = &["http", "https", "about", "data", "file"];
+
+/// Error returned by [`open_externally`].
+#[derive(Debug)]
+pub enum LaunchError {
+    /// Neither `$BROWSER` nor any of the fallback launchers could be spawned.
+    NoHandlerAvailable,
+}
+
+impl std::fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LaunchError::NoHandlerAvailable => {
+                write!(f, "no external URL handler available ($BROWSER, xdg-open, gio, gvfs-open, gnome-open all failed to launch)")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LaunchError {}
+
+/// Returns whether a navigation to `url` (or a forced "open externally"
+/// action, when `forced_external` is set) should be delegated to the system
+/// handler instead of rendered by HiWave.
+pub fn should_delegate(url: &str, forced_external: bool) -> bool {
+    if forced_external {
+        return true;
+    }
+    match url.split_once(':') {
+        Some((scheme, _)) => !NATIVE_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()),
+        None => false,
+    }
+}
+
+/// Hands `url` off to the platform's default browser/handler, trying
+/// `$BROWSER` (each `:`-separated entry, in order) before falling back
+/// through `xdg-open`, `gio open`, `gvfs-open`, and `gnome-open`.
+///
+/// Returns as soon as a candidate launcher spawns successfully; it does not
+/// wait for the child to exit, so a launcher that spawns but then fails
+/// (e.g. no default app configured) is not detected here.
+pub fn open_externally(url: &str) -> Result<(), LaunchError> {
+    for candidate in candidates() {
+        if spawn_detached(&candidate, url) {
+            return Ok(());
+        }
+    }
+    Err(LaunchError::NoHandlerAvailable)
+}
+
+/// Launcher commands to try, in order: each `$BROWSER` entry first, then the
+/// fixed fallback chain.
+fn candidates() -> Vec<Vec<String>> {
+    let mut candidates = Vec::new();
+
+    if let Ok(browser_env) = std::env::var("BROWSER") {
+        for entry in browser_env.split(':') {
+            // An entry may carry its own arguments (e.g. `firefox %s`).
+            let command: Vec<String> = entry.split_whitespace().map(str::to_string).collect();
+            if !command.is_empty() {
+                candidates.push(command);
+            }
+        }
+    }
+
+    candidates.push(vec!["xdg-open".to_string()]);
+    candidates.push(vec!["gio".to_string(), "open".to_string()]);
+    candidates.push(vec!["gvfs-open".to_string()]);
+    candidates.push(vec!["gnome-open".to_string()]);
+
+    candidates
+}
+
+/// Builds the final argument list for a launcher invoked against `url`: a
+/// `%s` in any argument is replaced with `url` (the `$BROWSER` convention);
+/// otherwise `url` is appended as the final argument.
+fn build_args(args: &[String], url: &str) -> Vec<String> {
+    if args.iter().any(|arg| arg.contains("%s")) {
+        args.iter().map(|arg| arg.replace("%s", url)).collect()
+    } else {
+        args.iter().cloned().chain(std::iter::once(url.to_string())).collect()
+    }
+}
+
+/// Spawns `command` against `url`, returning whether the spawn itself
+/// succeeded. A `%s` in any argument is replaced with `url` (the `$BROWSER`
+/// convention); otherwise `url` is appended as the final argument.
+fn spawn_detached(command: &[String], url: &str) -> bool {
+    let Some((program, args)) = command.split_first() else {
+        return false;
+    };
+
+    let mut cmd = Command::new(program);
+    cmd.args(build_args(args, url));
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    // Spawn and hand the `Child` to a reaper thread instead of waiting on it
+    // (or dropping it) here: the caller should not block on when the
+    // external handler exits, but dropping a `Child` outright leaves it
+    // unreaped until HiWave itself exits.
+    match cmd.spawn() {
+        Ok(mut child) => {
+            thread::spawn(move || {
+                let _ = child.wait();
+            });
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forced_external_always_delegates() {
+        assert!(should_delegate("https://example.com", true));
+    }
+
+    #[test]
+    fn native_schemes_are_not_delegated() {
+        assert!(!should_delegate("https://example.com", false));
+        assert!(!should_delegate("about:blank", false));
+        assert!(!should_delegate("file:///tmp/x.html", false));
+    }
+
+    #[test]
+    fn non_native_schemes_are_delegated() {
+        assert!(should_delegate("mailto:test@example.com", false));
+        assert!(should_delegate("magnet:?xt=urn:btih:abc", false));
+        assert!(should_delegate("tel:+15555550100", false));
+    }
+
+    #[test]
+    fn scheme_matching_is_case_insensitive() {
+        assert!(!should_delegate("HTTPS://example.com", false));
+    }
+
+    #[test]
+    fn urls_without_a_scheme_are_not_delegated() {
+        assert!(!should_delegate("not-a-url", false));
+    }
+
+    #[test]
+    fn build_args_substitutes_percent_s_placeholder() {
+        let args = vec!["open".to_string(), "%s".to_string(), "--flag".to_string()];
+        assert_eq!(
+            build_args(&args, "https://example.com"),
+            vec!["open".to_string(), "https://example.com".to_string(), "--flag".to_string()],
+        );
+    }
+
+    #[test]
+    fn build_args_appends_url_when_no_placeholder() {
+        let args = vec!["open".to_string()];
+        assert_eq!(
+            build_args(&args, "https://example.com"),
+            vec!["open".to_string(), "https://example.com".to_string()],
+        );
+    }
+
+    // Both exercised in one test (rather than two `#[test]`s) since they
+    // share the `BROWSER` process environment variable and `cargo test` runs
+    // tests concurrently by default.
+    #[test]
+    fn candidates_orders_browser_env_entries_then_the_fallback_chain() {
+        std::env::remove_var("BROWSER");
+        assert_eq!(
+            candidates(),
+            vec![
+                vec!["xdg-open".to_string()],
+                vec!["gio".to_string(), "open".to_string()],
+                vec!["gvfs-open".to_string()],
+                vec!["gnome-open".to_string()],
+            ],
+            "fallback chain with no $BROWSER set",
+        );
+
+        std::env::set_var("BROWSER", "firefox::chromium:");
+        let with_env = candidates();
+        std::env::remove_var("BROWSER");
+        assert_eq!(with_env[0], vec!["firefox".to_string()]);
+        assert_eq!(with_env[1], vec!["chromium".to_string()]);
+        assert_eq!(with_env.len(), 6, "empty $BROWSER entries are skipped");
+    }
+}
@@ -0,0 +1,322 @@
+//! System font discovery and glyph rasterization.
+//!
+//! Resolves CSS `font-family`/weight/style/lang queries to concrete font
+//! files via fontconfig, loads faces with FreeType, and rasterizes glyphs
+//! with hinting and subpixel positioning. Both the fontconfig match and the
+//! rasterized bitmap are cached - the former because pattern matching walks
+//! the system font cache on every miss, the latter so the layout engine
+//! never pays for the same `(face, size, glyph, subpixel offset)` twice in a
+//! frame.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use freetype::Library as FtLibrary;
+use freetype::face::Face as FtFace;
+
+/// Identifies a font face loaded by a [`FontDb`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FaceId(u32);
+
+/// CSS `font-style` value used when matching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+    Oblique,
+}
+
+/// Error returned by [`FontDb`] queries and rasterization.
+#[derive(Debug)]
+pub enum FontError {
+    /// fontconfig found no face for the query, even after fallback.
+    NoMatch,
+    /// `face` doesn't refer to any face currently loaded by this `FontDb`
+    /// (e.g. a stale or otherwise invalid [`FaceId`]).
+    UnknownFace,
+    /// `glyph_id` does not exist in `face`'s current charmap.
+    UnknownGlyph,
+    /// FreeType failed to load or rasterize the glyph.
+    FreeType(String),
+}
+
+impl std::fmt::Display for FontError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FontError::NoMatch => write!(f, "no matching font face"),
+            FontError::UnknownFace => write!(f, "face not loaded"),
+            FontError::UnknownGlyph => write!(f, "glyph not present in face"),
+            FontError::FreeType(msg) => write!(f, "FreeType error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+/// A rasterized glyph bitmap, ready for upload into the compositor's glyph
+/// atlas texture.
+pub struct RasterizedGlyph {
+    pub width: u32,
+    pub height: u32,
+    /// Offset from the pen position to the bitmap's top-left corner.
+    pub bearing_x: i32,
+    pub bearing_y: i32,
+    /// 8-bit coverage mask, `width * height` bytes.
+    pub bitmap: Vec<u8>,
+}
+
+struct LoadedFace {
+    ft_face: FtFace,
+    path: PathBuf,
+}
+
+/// Key identifying one rasterized glyph in the atlas cache. The subpixel
+/// offset is quantized to quarter-pixel steps so hinting jitter can't grow
+/// the cache unbounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    face: FaceId,
+    size_px: u32,
+    glyph_id: u32,
+    subpixel_quarter: u8,
+}
+
+/// Key identifying one fontconfig pattern match, so repeat queries for the
+/// same family/weight/style/lang skip the match entirely.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct QueryKey {
+    family: String,
+    weight: u16,
+    style: FontStyle,
+    lang: Option<String>,
+}
+
+/// Resolves CSS font queries to concrete faces and rasterizes their glyphs.
+///
+/// Exposed to the layout engine as the sole entry point into system font
+/// handling; layout code never talks to fontconfig or FreeType directly.
+pub struct FontDb {
+    ft_library: FtLibrary,
+    faces: Mutex<HashMap<FaceId, LoadedFace>>,
+    next_face_id: Mutex<u32>,
+    query_cache: Mutex<HashMap<QueryKey, FaceId>>,
+    glyph_cache: Mutex<HashMap<GlyphKey, Arc<RasterizedGlyph>>>,
+}
+
+impl FontDb {
+    /// Initializes FreeType. Fontconfig itself keeps process-global state
+    /// and needs no handle here.
+    pub fn new() -> Result<Self, FontError> {
+        let ft_library = FtLibrary::init().map_err(|e| FontError::FreeType(e.to_string()))?;
+        Ok(Self {
+            ft_library,
+            faces: Mutex::new(HashMap::new()),
+            next_face_id: Mutex::new(0),
+            query_cache: Mutex::new(HashMap::new()),
+            glyph_cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Resolves a CSS `font-family`/`font-weight`/`font-style` query to a
+    /// loaded face, matching fontconfig's configured substitution and
+    /// fallback rules for the current locale. `lang` narrows fallback to a
+    /// specific language's preferred fonts (e.g. distinguishing CJK variants)
+    /// and may be omitted.
+    pub fn query(
+        &self,
+        family: &str,
+        weight: u16,
+        style: FontStyle,
+        lang: Option<&str>,
+    ) -> Result<FaceId, FontError> {
+        let key = QueryKey {
+            family: family.to_string(),
+            weight,
+            style,
+            lang: lang.map(str::to_string),
+        };
+        if let Some(&cached) = self.query_cache.lock().unwrap().get(&key) {
+            return Ok(cached);
+        }
+
+        let path = fontconfig_match(family, weight, style, lang).ok_or(FontError::NoMatch)?;
+        let face_id = self.load_face(path)?;
+        self.query_cache.lock().unwrap().insert(key, face_id);
+        Ok(face_id)
+    }
+
+    /// Finds a fallback face covering `codepoint` when the face returned by
+    /// [`query`](Self::query) lacks a glyph for it - the CJK/emoji case,
+    /// walking fontconfig's full fallback chain for `family` rather than
+    /// just its first match.
+    pub fn fallback_for(
+        &self,
+        family: &str,
+        weight: u16,
+        style: FontStyle,
+        codepoint: char,
+    ) -> Result<FaceId, FontError> {
+        let path = fontconfig_fallback_for_char(family, weight, style, codepoint)
+            .ok_or(FontError::NoMatch)?;
+        self.load_face(path)
+    }
+
+    fn load_face(&self, path: PathBuf) -> Result<FaceId, FontError> {
+        let ft_face = self
+            .ft_library
+            .new_face(&path, 0)
+            .map_err(|e| FontError::FreeType(e.to_string()))?;
+
+        let mut next_id = self.next_face_id.lock().unwrap();
+        let face_id = FaceId(*next_id);
+        *next_id += 1;
+        self.faces
+            .lock()
+            .unwrap()
+            .insert(face_id, LoadedFace { ft_face, path });
+        Ok(face_id)
+    }
+
+    /// Rasterizes `glyph_id` from `face` at `size_px`, hinted and positioned
+    /// at `subpixel_offset` (fractional pixels, `0.0..1.0`) along the pen
+    /// direction. Repeated calls with the same arguments hit the glyph atlas
+    /// cache instead of re-rasterizing.
+    ///
+    /// Returns [`FontError::UnknownFace`] if `face` isn't a face loaded by
+    /// this `FontDb`, or [`FontError::UnknownGlyph`] if `face` is valid but
+    /// has no such `glyph_id` in its charmap.
+    pub fn rasterize(
+        &self,
+        face: FaceId,
+        glyph_id: u32,
+        size_px: f32,
+        subpixel_offset: f32,
+    ) -> Result<Arc<RasterizedGlyph>, FontError> {
+        let key = GlyphKey {
+            face,
+            size_px: size_px.round() as u32,
+            glyph_id,
+            subpixel_quarter: ((subpixel_offset.clamp(0.0, 1.0)) * 4.0).round() as u8,
+        };
+
+        if let Some(cached) = self.glyph_cache.lock().unwrap().get(&key) {
+            return Ok(cached.clone());
+        }
+
+        let mut faces = self.faces.lock().unwrap();
+        let loaded = faces.get_mut(&face).ok_or(FontError::UnknownFace)?;
+        loaded
+            .ft_face
+            .set_pixel_sizes(0, key.size_px)
+            .map_err(|e| FontError::FreeType(e.to_string()))?;
+        // Shift the outline by the quantized subpixel offset (26.6 fixed
+        // point: a quarter pixel is 16 units) before it is hinted and rendered.
+        let mut matrix = freetype::Matrix { xx: 0x10000, xy: 0, yx: 0, yy: 0x10000 };
+        let mut delta = freetype::Vector {
+            x: key.subpixel_quarter as std::os::raw::c_long * 16,
+            y: 0,
+        };
+        loaded.ft_face.set_transform(&mut matrix, &mut delta);
+        loaded
+            .ft_face
+            .load_glyph(
+                glyph_id,
+                freetype::face::LoadFlag::DEFAULT | freetype::face::LoadFlag::TARGET_NORMAL,
+            )
+            .map_err(|e| match e {
+                freetype::Error::InvalidGlyphIndex => FontError::UnknownGlyph,
+                other => FontError::FreeType(other.to_string()),
+            })?;
+
+        let glyph_slot = loaded.ft_face.glyph();
+        glyph_slot
+            .render_glyph(freetype::render_mode::RenderMode::Normal)
+            .map_err(|e| FontError::FreeType(e.to_string()))?;
+        let bitmap = glyph_slot.bitmap();
+
+        let rasterized = Arc::new(RasterizedGlyph {
+            width: bitmap.width() as u32,
+            height: bitmap.rows() as u32,
+            bearing_x: glyph_slot.bitmap_left(),
+            bearing_y: glyph_slot.bitmap_top(),
+            bitmap: bitmap.buffer().to_vec(),
+        });
+
+        drop(faces);
+        self.glyph_cache
+            .lock()
+            .unwrap()
+            .insert(key, rasterized.clone());
+        Ok(rasterized)
+    }
+}
+
+/// Runs a fontconfig pattern match for `family`/`weight`/`style`/`lang` and
+/// returns the path fontconfig resolved it to, honoring the system's
+/// configured substitution and fallback rules.
+fn fontconfig_match(
+    family: &str,
+    weight: u16,
+    style: FontStyle,
+    lang: Option<&str>,
+) -> Option<PathBuf> {
+    let mut pattern = fontconfig::Pattern::new();
+    pattern.add_string("family", family);
+    pattern.add_integer("weight", fontconfig_weight(weight));
+    pattern.add_integer("slant", fontconfig_slant(style));
+    if let Some(lang) = lang {
+        pattern.add_string("lang", lang);
+    }
+    pattern.config_substitute(fontconfig::MatchKind::Pattern);
+    pattern.default_substitute();
+
+    let matched = pattern.font_match()?;
+    matched.get_string("file").map(PathBuf::from)
+}
+
+/// Walks fontconfig's fallback chain for `family`/`weight`/`style`, in
+/// preference order, and returns the first face whose charset contains
+/// `codepoint`.
+fn fontconfig_fallback_for_char(
+    family: &str,
+    weight: u16,
+    style: FontStyle,
+    codepoint: char,
+) -> Option<PathBuf> {
+    let mut pattern = fontconfig::Pattern::new();
+    pattern.add_string("family", family);
+    pattern.add_integer("weight", fontconfig_weight(weight));
+    pattern.add_integer("slant", fontconfig_slant(style));
+    pattern.config_substitute(fontconfig::MatchKind::Pattern);
+    pattern.default_substitute();
+
+    pattern
+        .font_sort()
+        .into_iter()
+        .find(|candidate| candidate.charset_has_char(codepoint))
+        .and_then(|candidate| candidate.get_string("file").map(PathBuf::from))
+}
+
+/// Maps a CSS numeric font-weight (100-900) to fontconfig's weight scale.
+fn fontconfig_weight(css_weight: u16) -> i32 {
+    match css_weight {
+        ..=149 => fontconfig::FC_WEIGHT_THIN,
+        150..=249 => fontconfig::FC_WEIGHT_EXTRALIGHT,
+        250..=349 => fontconfig::FC_WEIGHT_LIGHT,
+        350..=449 => fontconfig::FC_WEIGHT_REGULAR,
+        450..=549 => fontconfig::FC_WEIGHT_MEDIUM,
+        550..=649 => fontconfig::FC_WEIGHT_SEMIBOLD,
+        650..=749 => fontconfig::FC_WEIGHT_BOLD,
+        750..=849 => fontconfig::FC_WEIGHT_EXTRABOLD,
+        _ => fontconfig::FC_WEIGHT_BLACK,
+    }
+}
+
+fn fontconfig_slant(style: FontStyle) -> i32 {
+    match style {
+        FontStyle::Normal => fontconfig::FC_SLANT_ROMAN,
+        FontStyle::Italic => fontconfig::FC_SLANT_ITALIC,
+        FontStyle::Oblique => fontconfig::FC_SLANT_OBLIQUE,
+    }
+}
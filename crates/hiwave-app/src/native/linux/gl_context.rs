@@ -0,0 +1,225 @@
+//! GL/EGL rendering context for GPU-accelerated compositing.
+//!
+//! Bound to the same native surface `run_native` creates for RustKit, this
+//! negotiates an EGL config (on both X11 and Wayland; there is no GLX path,
+//! so an X11 display without EGL fails negotiation),
+//! creates a context and a window surface tied to the compositor surface,
+//! and exposes [`RenderContext::make_current`]/[`RenderContext::swap_buffers`]
+//! so the compositor can scroll and paint without software-blitting every
+//! frame. [`RenderBackend::Software`] skips all of this and keeps the
+//! existing blit path for systems without a usable GL driver.
+//!
+//! Wayland requires a surface's first buffer attach/commit to happen only
+//! after it has been configured by the compositor ([`xdg_surface.configure`]
+//! / `ack_configure`), so context creation is split in two:
+//! [`RenderContext::negotiate`] picks the EGL config and creates the
+//! context eagerly, while the `wl_egl_window` (and the EGL window surface
+//! built on top of it) isn't created until [`RenderContext::attach`] is
+//! called after the first configure is acknowledged.
+//!
+//! [`xdg_surface.configure`]: https://wayland.app/protocols/xdg-shell#xdg_surface:event:configure
+
+use egl::{Config as EglConfig, Context as EglContext, Display as EglDisplay, Surface as EglSurface};
+
+/// Whether to use a hardware-accelerated GL context or keep HiWave's
+/// existing software blit path. Chosen via a `run_native` option rather than
+/// probed automatically, since "GL driver exists" and "GL driver is good
+/// enough to prefer" aren't the same question.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderBackend {
+    Hardware,
+    Software,
+}
+
+impl Default for RenderBackend {
+    fn default() -> Self {
+        RenderBackend::Software
+    }
+}
+
+/// Error returned by [`RenderContext`] setup and per-frame operations.
+#[derive(Debug)]
+pub enum GlError {
+    /// No EGL config satisfied HiWave's minimum requirements.
+    NoConfig,
+    ContextCreation(String),
+    MakeCurrent(String),
+    SwapBuffers(String),
+}
+
+impl std::fmt::Display for GlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GlError::NoConfig => write!(f, "no suitable EGL config found"),
+            GlError::ContextCreation(msg) => write!(f, "failed to create GL context: {msg}"),
+            GlError::MakeCurrent(msg) => write!(f, "failed to make GL context current: {msg}"),
+            GlError::SwapBuffers(msg) => write!(f, "failed to swap buffers: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for GlError {}
+
+/// A GL context not yet bound to a configured compositor surface. Created by
+/// [`PendingContext::negotiate`]; becomes a [`RenderContext`] once
+/// [`RenderContext::attach`] runs after the surface's first configure.
+pub struct NegotiatedContext {
+    display: EglDisplay,
+    config: EglConfig,
+    context: EglContext,
+}
+
+/// The result of [`PendingContext::negotiate`]: either a hardware context
+/// waiting to be [`attach`](RenderContext::attach)ed to a configured
+/// surface, or the software fallback (which needs no attach step).
+pub enum PendingContext {
+    Hardware(NegotiatedContext),
+    Software,
+}
+
+impl PendingContext {
+    /// Connects to the EGL display for `window` and picks a config and
+    /// context, without creating a window surface yet - Wayland forbids
+    /// attaching a buffer before the surface has been configured, so that
+    /// step waits for [`RenderContext::attach`].
+    pub fn negotiate(
+        window: &dyn raw_window_handle::HasRawDisplayHandle,
+        backend: RenderBackend,
+    ) -> Result<Self, GlError> {
+        if backend == RenderBackend::Software {
+            return Ok(PendingContext::Software);
+        }
+
+        let display = egl::Display::from_native(window)
+            .map_err(|e| GlError::ContextCreation(e.to_string()))?;
+        let config = display
+            .choose_config(&EGL_CONFIG_ATTRIBS)
+            .ok_or(GlError::NoConfig)?;
+        let context = display
+            .create_context(&config, &EGL_CONTEXT_ATTRIBS)
+            .map_err(|e| GlError::ContextCreation(e.to_string()))?;
+
+        Ok(PendingContext::Hardware(NegotiatedContext {
+            display,
+            config,
+            context,
+        }))
+    }
+}
+
+/// A GL/EGL context bound to the compositor surface, ready to render.
+pub enum RenderContext {
+    Hardware {
+        display: EglDisplay,
+        context: EglContext,
+        surface: EglSurface,
+        /// Current logical size and DPI scale, tracked so `resize` can tell
+        /// whether the backing surface actually needs to change.
+        size: (u32, u32),
+        scale_factor: f64,
+    },
+    Software,
+}
+
+const EGL_CONFIG_ATTRIBS: [i32; 11] = [
+    egl::RED_SIZE,
+    8,
+    egl::GREEN_SIZE,
+    8,
+    egl::BLUE_SIZE,
+    8,
+    egl::ALPHA_SIZE,
+    8,
+    egl::SURFACE_TYPE,
+    egl::WINDOW_BIT,
+    egl::NONE,
+];
+
+const EGL_CONTEXT_ATTRIBS: [i32; 3] = [egl::CONTEXT_CLIENT_VERSION, 2, egl::NONE];
+
+impl RenderContext {
+    /// Creates the window surface once the compositor has configured it
+    /// (i.e. after the first `xdg_surface.configure` is acknowledged), and
+    /// makes the resulting context current.
+    ///
+    /// `pending` is produced by [`PendingContext::negotiate`]; attaching a
+    /// [`PendingContext::Software`] just yields [`RenderContext::Software`].
+    pub fn attach(
+        pending: PendingContext,
+        native_window: &dyn raw_window_handle::HasRawWindowHandle,
+        width: u32,
+        height: u32,
+        scale_factor: f64,
+    ) -> Result<Self, GlError> {
+        let negotiated = match pending {
+            PendingContext::Software => return Ok(RenderContext::Software),
+            PendingContext::Hardware(negotiated) => negotiated,
+        };
+
+        let surface = negotiated
+            .display
+            .create_window_surface(&negotiated.config, native_window)
+            .map_err(|e| GlError::ContextCreation(e.to_string()))?;
+
+        let context = RenderContext::Hardware {
+            display: negotiated.display,
+            context: negotiated.context,
+            surface,
+            size: (width, height),
+            scale_factor,
+        };
+        context.make_current()?;
+        Ok(context)
+    }
+
+    /// Makes this context current on the calling thread. A no-op for
+    /// [`RenderContext::Software`].
+    pub fn make_current(&self) -> Result<(), GlError> {
+        match self {
+            RenderContext::Hardware {
+                display,
+                context,
+                surface,
+                ..
+            } => display
+                .make_current(context, surface)
+                .map_err(|e| GlError::MakeCurrent(e.to_string())),
+            RenderContext::Software => Ok(()),
+        }
+    }
+
+    /// Presents the frame rendered since the last `swap_buffers`. A no-op
+    /// for [`RenderContext::Software`], which blits directly instead.
+    pub fn swap_buffers(&self) -> Result<(), GlError> {
+        match self {
+            RenderContext::Hardware {
+                display, surface, ..
+            } => display
+                .swap_buffers(surface)
+                .map_err(|e| GlError::SwapBuffers(e.to_string())),
+            RenderContext::Software => Ok(()),
+        }
+    }
+
+    /// Resizes the backing surface to `width`x`height` logical pixels at
+    /// `scale_factor`, e.g. in response to a window resize or the window
+    /// moving to a monitor with a different DPI scale.
+    pub fn resize(&mut self, width: u32, height: u32, scale_factor: f64) {
+        if let RenderContext::Hardware {
+            surface,
+            size,
+            scale_factor: current_scale,
+            ..
+        } = self
+        {
+            if *size == (width, height) && *current_scale == scale_factor {
+                return;
+            }
+            let pixel_width = (width as f64 * scale_factor).round() as u32;
+            let pixel_height = (height as f64 * scale_factor).round() as u32;
+            surface.resize(pixel_width, pixel_height);
+            *size = (width, height);
+            *current_scale = scale_factor;
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Per-window (per-surface) keyboard layout persistence under Wayland.
+//!
+//! Wayland keeps the active xkb group on the seat, not per top-level
+//! surface, so switching languages in one HiWave window leaks into every
+//! other window sharing the seat. When
+//! [`NativeOptions::restore_window_keyboard_layout`](super::NativeOptions::restore_window_keyboard_layout)
+//! is enabled, this tracker remembers the last xkb group used by each
+//! surface and `run_native` restores it whenever that surface regains
+//! keyboard focus.
+//!
+//! Layouts are tracked by name rather than raw group index: a keymap reload
+//! can renumber groups, so the stored name is re-resolved against the
+//! *current* keymap's layout list on restore instead of trusting a stale
+//! index.
+
+use std::collections::HashMap;
+
+/// Identifies a Wayland top-level surface for the lifetime of this process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SurfaceId(pub(crate) u32);
+
+/// Tracks each surface's last-used xkb layout so it can be restored on
+/// refocus instead of leaking the seat-wide layout between windows.
+#[derive(Default)]
+pub(crate) struct KeyboardLayoutTracker {
+    /// Layout name last used by each surface, keyed by surface id.
+    last_used: HashMap<SurfaceId, String>,
+    /// Layout names in the seat's current keymap, indexed by group.
+    current_layouts: Vec<String>,
+}
+
+impl KeyboardLayoutTracker {
+    /// Records a new keymap's layout list so future `enter`/`leave` events
+    /// can resolve group indices to names (and back) against it. Called on
+    /// `SurfaceEvent::KeymapChanged`.
+    pub(crate) fn set_keymap(&mut self, layout_names: Vec<String>) {
+        self.current_layouts = layout_names;
+    }
+
+    /// Returns the group index `surface` should be switched to on focus, if
+    /// it has a remembered layout that still exists in the current keymap.
+    /// Called on `SurfaceEvent::KeyboardEnter`.
+    pub(crate) fn restore_group_for(&self, surface: SurfaceId) -> Option<u32> {
+        let name = self.last_used.get(&surface)?;
+        self.current_layouts
+            .iter()
+            .position(|n| n == name)
+            .map(|i| i as u32)
+    }
+
+    /// Remembers `group` as the layout last used by `surface`. Called on
+    /// `SurfaceEvent::KeyboardLeave` and whenever the active group changes
+    /// while a surface is focused.
+    pub(crate) fn remember(&mut self, surface: SurfaceId, group: u32) {
+        if let Some(name) = self.current_layouts.get(group as usize) {
+            self.last_used.insert(surface, name.clone());
+        }
+    }
+
+    /// Drops the tracked layout for a surface that no longer exists,
+    /// including one destroyed while it held keyboard focus. Called on
+    /// `SurfaceEvent::SurfaceDestroyed`.
+    pub(crate) fn forget(&mut self, surface: SurfaceId) {
+        self.last_used.remove(&surface);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remember_then_restore_round_trips_the_group() {
+        let mut tracker = KeyboardLayoutTracker::default();
+        let surface = SurfaceId(1);
+        tracker.set_keymap(vec!["us".to_string(), "de".to_string()]);
+
+        tracker.remember(surface, 1);
+
+        assert_eq!(tracker.restore_group_for(surface), Some(1));
+    }
+
+    #[test]
+    fn restore_resolves_by_name_after_a_keymap_reload_reorders_groups() {
+        let mut tracker = KeyboardLayoutTracker::default();
+        let surface = SurfaceId(1);
+        tracker.set_keymap(vec!["us".to_string(), "de".to_string()]);
+        tracker.remember(surface, 1); // "de"
+
+        // Keymap reloaded: "de" is now group 0 instead of group 1.
+        tracker.set_keymap(vec!["de".to_string(), "us".to_string()]);
+
+        assert_eq!(tracker.restore_group_for(surface), Some(0));
+    }
+
+    #[test]
+    fn restore_is_none_when_the_remembered_name_no_longer_exists() {
+        let mut tracker = KeyboardLayoutTracker::default();
+        let surface = SurfaceId(1);
+        tracker.set_keymap(vec!["us".to_string(), "de".to_string()]);
+        tracker.remember(surface, 1); // "de"
+
+        // Keymap reloaded without "de" at all.
+        tracker.set_keymap(vec!["us".to_string(), "fr".to_string()]);
+
+        assert_eq!(tracker.restore_group_for(surface), None);
+    }
+
+    #[test]
+    fn restore_is_none_for_a_surface_with_no_remembered_layout() {
+        let tracker = KeyboardLayoutTracker::default();
+        assert_eq!(tracker.restore_group_for(SurfaceId(1)), None);
+    }
+
+    #[test]
+    fn remember_ignores_a_group_index_outside_the_current_keymap() {
+        let mut tracker = KeyboardLayoutTracker::default();
+        let surface = SurfaceId(1);
+        tracker.set_keymap(vec!["us".to_string()]);
+
+        tracker.remember(surface, 5);
+
+        assert_eq!(tracker.restore_group_for(surface), None);
+    }
+
+    #[test]
+    fn forget_drops_a_surface_that_currently_holds_focus() {
+        let mut tracker = KeyboardLayoutTracker::default();
+        let surface = SurfaceId(1);
+        tracker.set_keymap(vec!["us".to_string(), "de".to_string()]);
+        tracker.remember(surface, 1);
+        assert_eq!(tracker.restore_group_for(surface), Some(1));
+
+        // Surface destroyed while it still held keyboard focus.
+        tracker.forget(surface);
+
+        assert_eq!(tracker.restore_group_for(surface), None);
+    }
+
+    #[test]
+    fn forget_does_not_affect_other_surfaces() {
+        let mut tracker = KeyboardLayoutTracker::default();
+        let (a, b) = (SurfaceId(1), SurfaceId(2));
+        tracker.set_keymap(vec!["us".to_string(), "de".to_string()]);
+        tracker.remember(a, 0);
+        tracker.remember(b, 1);
+
+        tracker.forget(a);
+
+        assert_eq!(tracker.restore_group_for(a), None);
+        assert_eq!(tracker.restore_group_for(b), Some(1));
+    }
+}
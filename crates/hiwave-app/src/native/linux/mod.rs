@@ -0,0 +1,270 @@
+//! Linux-native browser implementation (X11/Wayland) backed by the in-house
+//! RustKit rendering surface.
+//!
+//! This module intentionally avoids wry/tao: the Linux target talks to the
+//! windowing system and RustKit directly so embedders get a small, auditable
+//! surface instead of a full WebView abstraction layer.
+
+mod bridge;
+mod clipboard;
+mod cursor;
+mod external_protocol;
+mod fonts;
+mod gl_context;
+mod keyboard_layout;
+
+pub use bridge::{InvokeHandler, InvokeMessage};
+pub use clipboard::{Clipboard, ClipboardError, SelectionKind};
+pub use cursor::{CursorError, CursorIcon};
+pub use external_protocol::{open_externally, should_delegate, LaunchError};
+pub use fonts::{FaceId, FontDb, FontError, FontStyle, RasterizedGlyph};
+pub use gl_context::{GlError, PendingContext, RenderBackend, RenderContext};
+pub use keyboard_layout::SurfaceId;
+
+use std::fmt;
+
+use rustkit::{Surface, SurfaceEvent};
+
+use keyboard_layout::KeyboardLayoutTracker;
+
+/// Configuration accepted by [`run_native`].
+pub struct NativeOptions {
+    /// Handler for `external.invoke(...)` calls made by page JavaScript.
+    /// See the [`bridge`] module for the full contract.
+    pub invoke_handler: Option<InvokeHandler>,
+    /// Restore each window's own xkb keyboard layout on focus instead of
+    /// leaking Wayland's seat-wide layout between windows. See
+    /// [`keyboard_layout`] for the full contract. Ignored on X11, where
+    /// layout is already tracked per-window by the server.
+    pub restore_window_keyboard_layout: bool,
+    /// Whether to render through a hardware GL/EGL context or keep
+    /// software-blitting. See [`gl_context`] for the full contract.
+    pub render_backend: RenderBackend,
+    /// XCursor theme to use when the compositor doesn't support
+    /// `wp_cursor_shape_device_v1`. Defaults to `"default"`.
+    pub cursor_theme: String,
+}
+
+impl Default for NativeOptions {
+    fn default() -> Self {
+        Self {
+            invoke_handler: None,
+            restore_window_keyboard_layout: false,
+            render_backend: RenderBackend::default(),
+            cursor_theme: "default".to_string(),
+        }
+    }
+}
+
+/// Error returned by [`run_native`] and the APIs it hands out.
+#[derive(Debug)]
+pub enum NativeError {
+    /// Failed to connect to the X11/Wayland display server.
+    NoDisplay,
+    /// RustKit surface initialization failed.
+    Surface(String),
+    /// The font subsystem failed to initialize (e.g. FreeType init failure).
+    Fonts(FontError),
+}
+
+impl fmt::Display for NativeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NativeError::NoDisplay => write!(f, "no X11 or Wayland display available"),
+            NativeError::Surface(msg) => write!(f, "RustKit surface error: {msg}"),
+            NativeError::Fonts(err) => write!(f, "font subsystem error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for NativeError {}
+
+/// A running native browser window.
+///
+/// This is the single point of contact between the native module and the
+/// RustKit surface: it is handed to the registered [`InvokeHandler`], and
+/// will grow further accessors as more native subsystems land.
+pub struct NativeView {
+    surface: Surface,
+    invoke_queue: bridge::InvokeQueue,
+    keyboard_layout: KeyboardLayoutTracker,
+    /// Surface currently holding keyboard focus, if any. Tracked so a
+    /// seat-wide `KeymapChanged` (group switched via a shortcut, with no
+    /// intervening `KeyboardLeave`) can still be attributed to the right
+    /// surface.
+    keyboard_focus: Option<SurfaceId>,
+    clipboard: Clipboard,
+    font_db: FontDb,
+    /// Negotiated but not yet attached to a configured surface. Taken and
+    /// replaced by `gl` on the first `SurfaceEvent::Configured`.
+    pending_gl: Option<PendingContext>,
+    gl: Option<RenderContext>,
+    cursors: cursor::CursorLoader,
+    cursor_theme: String,
+    pointer_scale: u32,
+}
+
+impl NativeView {
+    /// Evaluates `script` in the page's JavaScript context.
+    ///
+    /// This is the Rust→JS half of the bridge: handlers registered via
+    /// [`NativeOptions::invoke_handler`] call this to push results back into
+    /// the page after handling an `external.invoke(...)` call.
+    pub fn eval(&self, script: &str) {
+        self.surface.eval_script(script);
+    }
+
+    /// The system clipboard and primary-selection access exposed to page
+    /// content.
+    pub fn clipboard(&self) -> &Clipboard {
+        &self.clipboard
+    }
+
+    /// The font database used to resolve and rasterize fonts for layout.
+    pub fn fonts(&self) -> &FontDb {
+        &self.font_db
+    }
+
+    /// The GL/EGL rendering context, once the surface has been configured by
+    /// the compositor. `None` until the first `SurfaceEvent::Configured`;
+    /// after that it is always `Some`, including under
+    /// [`RenderBackend::Software`] (as `RenderContext::Software`), so
+    /// callers have one place to check regardless of backend.
+    pub fn gl(&self) -> Option<&RenderContext> {
+        self.gl.as_ref()
+    }
+
+    /// Sets the pointer's cursor to `icon`, using the compositor's
+    /// `wp_cursor_shape_device_v1` protocol where available and an XCursor
+    /// theme lookup otherwise. See [`cursor`] for the full contract.
+    pub fn set_cursor(&self, icon: CursorIcon) -> Result<(), CursorError> {
+        self.cursors
+            .set_cursor(&self.surface, icon, &self.cursor_theme, self.pointer_scale)
+    }
+}
+
+/// Boots the HiWave browser window and runs the native event loop until the
+/// window is closed.
+pub fn run_native(options: NativeOptions) -> Result<(), NativeError> {
+    let surface = Surface::connect().map_err(|e| NativeError::Surface(e.to_string()))?;
+    let clipboard = Clipboard::spawn(surface.selection_backend());
+    let font_db = FontDb::new().map_err(NativeError::Fonts)?;
+    let pending_gl = PendingContext::negotiate(&surface, options.render_backend)
+        .map_err(|e| NativeError::Surface(e.to_string()))?;
+    let cursors = cursor::CursorLoader::new(surface.supports_cursor_shape());
+    let mut view = NativeView {
+        surface,
+        invoke_queue: bridge::InvokeQueue::default(),
+        keyboard_layout: KeyboardLayoutTracker::default(),
+        keyboard_focus: None,
+        clipboard,
+        font_db,
+        pending_gl: Some(pending_gl),
+        gl: None,
+        cursors,
+        cursor_theme: options.cursor_theme.clone(),
+        pointer_scale: 1,
+    };
+
+    bridge::install(&view, options.invoke_handler.as_ref());
+
+    loop {
+        match view.surface.next_event() {
+            SurfaceEvent::DocumentReady => {
+                view.invoke_queue.mark_ready();
+                bridge::flush(&view, options.invoke_handler.as_ref());
+            }
+            SurfaceEvent::NavigationStarted => {
+                bridge::install(&view, options.invoke_handler.as_ref());
+            }
+            SurfaceEvent::Invoke(message) => {
+                bridge::dispatch(&view, options.invoke_handler.as_ref(), message);
+            }
+            SurfaceEvent::KeymapChanged {
+                layout_names,
+                layout_group,
+            } if options.restore_window_keyboard_layout => {
+                view.keyboard_layout.set_keymap(layout_names);
+                // The seat's group just changed with no intervening
+                // KeyboardLeave (e.g. a layout-cycling shortcut); attribute
+                // it to whichever surface currently holds keyboard focus so
+                // that surface's restored layout doesn't go stale.
+                if let Some(focused) = view.keyboard_focus {
+                    view.keyboard_layout.remember(focused, layout_group);
+                }
+            }
+            SurfaceEvent::KeyboardEnter { surface: id, .. }
+                if options.restore_window_keyboard_layout =>
+            {
+                view.keyboard_focus = Some(id);
+                if let Some(group) = view.keyboard_layout.restore_group_for(id) {
+                    view.surface.set_keyboard_layout_group(group);
+                }
+            }
+            SurfaceEvent::KeyboardLeave {
+                surface: id,
+                layout_group,
+            } if options.restore_window_keyboard_layout => {
+                view.keyboard_layout.remember(id, layout_group);
+                if view.keyboard_focus == Some(id) {
+                    view.keyboard_focus = None;
+                }
+            }
+            SurfaceEvent::SurfaceDestroyed(id) if options.restore_window_keyboard_layout => {
+                view.keyboard_layout.forget(id);
+                if view.keyboard_focus == Some(id) {
+                    view.keyboard_focus = None;
+                }
+            }
+            SurfaceEvent::NavigationRequested {
+                url,
+                forced_external,
+            } => {
+                if external_protocol::should_delegate(&url, forced_external) {
+                    // Best-effort: if no handler is available there is
+                    // nothing more HiWave can do with this URL.
+                    let _ = external_protocol::open_externally(&url);
+                } else {
+                    view.surface.navigate(&url);
+                }
+            }
+            SurfaceEvent::CursorHint(icon) => {
+                // Best-effort: if neither the cursor-shape protocol nor the
+                // XCursor theme has this icon, the pointer just keeps its
+                // previous shape.
+                let _ = view.set_cursor(icon);
+            }
+            SurfaceEvent::Configured {
+                width,
+                height,
+                scale_factor,
+            } => {
+                view.pointer_scale = scale_factor.round() as u32;
+                match view.gl.as_mut() {
+                    // Already attached: this is a resize/rescale configure.
+                    Some(gl) => gl.resize(width, height, scale_factor),
+                    // First configure: now it's safe to attach the backing
+                    // surface (Wayland forbids it before this point).
+                    None => {
+                        if let Some(pending) = view.pending_gl.take() {
+                            let attached = RenderContext::attach(
+                                pending,
+                                &view.surface,
+                                width,
+                                height,
+                                scale_factor,
+                            )
+                            .map_err(|e| NativeError::Surface(e.to_string()))?;
+                            view.gl = Some(attached);
+                        }
+                    }
+                }
+            }
+            SurfaceEvent::Closed => break,
+            SurfaceEvent::Other => {}
+            _ => {}
+        }
+    }
+
+    Ok(())
+}